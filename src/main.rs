@@ -4,7 +4,7 @@ use crossterm::{
 };
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::HashSet;
 use std::io;
 use std::sync::mpsc;
 use std::thread;
@@ -21,7 +21,12 @@ use tui::{
     Terminal,
 };
 
+mod store;
+mod suggest;
+use store::{JsonStore, SqliteStore, Store};
+
 const DB_PATH: &str = "./data/db.json";
+const SQLITE_URL: &str = "sqlite://./data/db.sqlite3";
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -29,6 +34,8 @@ pub enum Error {
     ReadDBError(#[from] io::Error),
     #[error("error parsing the DB file: {0}")]
     ParseDBError(#[from] serde_json::Error),
+    #[error("database error: {0}")]
+    DbError(#[from] sqlx::Error),
 }
 
 enum Event<I> {
@@ -37,11 +44,11 @@ enum Event<I> {
 }
 
 #[derive(Serialize, Deserialize, Clone)]
-struct Group {
-    name: String,
-    assignment: usize,
-    feedback: Vec<String>,
-    footnote: String,
+pub(crate) struct Group {
+    pub(crate) name: String,
+    pub(crate) assignment: usize,
+    pub(crate) feedback: Vec<String>,
+    pub(crate) footnote: String,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -53,7 +60,166 @@ enum MenuItem {
 
 enum InputMode {
     Normal,
-    // Editing,
+    Editing,
+}
+
+/// Vim-style split inside the feedback editor: `Insert` types into the line,
+/// `Command` interprets keys as cursor motions instead.
+enum EditorMode {
+    Insert,
+    Command,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+/// Moves to the start of the next word, where a word is a maximal run of alphanumerics.
+/// `pos` and the return value are byte offsets into `input`, matching how `cursor_pos`
+/// is used everywhere else (`input.insert`/`input.remove`, `$`/`^` motions).
+fn next_word_boundary(input: &str, pos: usize) -> usize {
+    let indices: Vec<(usize, char)> = input.char_indices().collect();
+    let len = indices.len();
+    let mut i = indices
+        .iter()
+        .position(|&(byte, _)| byte >= pos)
+        .unwrap_or(len);
+    while i < len && is_word_char(indices[i].1) {
+        i += 1;
+    }
+    while i < len && !is_word_char(indices[i].1) {
+        i += 1;
+    }
+    indices.get(i).map_or(input.len(), |&(byte, _)| byte)
+}
+
+/// Moves to the start of the previous word, where a word is a maximal run of alphanumerics.
+/// `pos` and the return value are byte offsets into `input`, see `next_word_boundary`.
+fn prev_word_boundary(input: &str, pos: usize) -> usize {
+    let indices: Vec<(usize, char)> = input.char_indices().collect();
+    let len = indices.len();
+    let mut i = indices
+        .iter()
+        .position(|&(byte, _)| byte >= pos)
+        .unwrap_or(len);
+    if i == 0 {
+        return 0;
+    }
+    i -= 1;
+    while i > 0 && !is_word_char(indices[i].1) {
+        i -= 1;
+    }
+    while i > 0 && is_word_char(indices[i - 1].1) {
+        i -= 1;
+    }
+    indices[i].0
+}
+
+/// The pane that currently receives `Up`/`Down`/`Enter` and is drawn with a
+/// highlighted border. `Tab` cycles within whichever pair belongs to the
+/// active menu item; in the feedback editor's insert mode it cycles the
+/// suggestion selection instead.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Focus {
+    GroupList,
+    Detail,
+    FeedbackEditor,
+    Suggestions,
+}
+
+impl Focus {
+    /// Toggles between the Groups menu's two panes
+    fn next_in_groups(self) -> Focus {
+        match self {
+            Focus::GroupList => Focus::Detail,
+            _ => Focus::GroupList,
+        }
+    }
+
+    /// Toggles between the Editing menu's two panes
+    fn next_in_editing(self) -> Focus {
+        match self {
+            Focus::FeedbackEditor => Focus::Suggestions,
+            _ => Focus::FeedbackEditor,
+        }
+    }
+}
+
+/// Border style and type for a pane, highlighted when it holds focus
+fn focus_chrome(is_focused: bool) -> (Style, BorderType) {
+    if is_focused {
+        (Style::default().fg(Color::Cyan), BorderType::Thick)
+    } else {
+        (Style::default().fg(Color::White), BorderType::Plain)
+    }
+}
+
+/// Indent depth, visibility and collapsed state shared by every tree row
+struct TreeItemInfo {
+    indent: usize,
+    visible: bool,
+    collapsed: bool,
+}
+
+/// A row in the assignment/group tree rendered in the left pane
+enum TreeItem {
+    Assignment {
+        assignment: usize,
+        group_count: usize,
+        info: TreeItemInfo,
+    },
+    Group {
+        group_index: usize,
+        info: TreeItemInfo,
+    },
+}
+
+impl TreeItem {
+    fn is_visible(&self) -> bool {
+        match self {
+            TreeItem::Assignment { info, .. } => info.visible,
+            TreeItem::Group { info, .. } => info.visible,
+        }
+    }
+}
+
+/// Flattens the group list into assignment parents and group children, collapsing
+/// the children of any assignment present in `collapsed`.
+fn build_group_tree(groups: &[Group], collapsed: &HashSet<usize>) -> Vec<TreeItem> {
+    let mut assignments: Vec<usize> = groups.iter().map(|group| group.assignment).collect();
+    assignments.sort_unstable();
+    assignments.dedup();
+
+    let mut tree = Vec::new();
+    for assignment in assignments {
+        let is_collapsed = collapsed.contains(&assignment);
+        let group_count = groups
+            .iter()
+            .filter(|group| group.assignment == assignment)
+            .count();
+        tree.push(TreeItem::Assignment {
+            assignment,
+            group_count,
+            info: TreeItemInfo {
+                indent: 0,
+                visible: true,
+                collapsed: is_collapsed,
+            },
+        });
+        for (group_index, group) in groups.iter().enumerate() {
+            if group.assignment == assignment {
+                tree.push(TreeItem::Group {
+                    group_index,
+                    info: TreeItemInfo {
+                        indent: 1,
+                        visible: !is_collapsed,
+                        collapsed: false,
+                    },
+                });
+            }
+        }
+    }
+    tree
 }
 
 /// App holds the state of the application
@@ -62,8 +228,28 @@ struct App {
     input: String,
     /// Current input mode
     input_mode: InputMode,
-    /// History of recorded messages
-    messages: Vec<String>,
+    /// Index into the group list of the group currently being edited
+    editing_index: Option<usize>,
+    /// Index into the edited group's feedback lines currently loaded into `input`
+    /// for editing, or `None` while `input` holds a new, not yet committed line
+    editing_line: Option<usize>,
+    /// Snapshots of the group list taken before each DB-mutating action
+    undo_stack: Vec<Vec<Group>>,
+    /// Snapshots popped off the undo stack, available to replay
+    redo_stack: Vec<Vec<Group>>,
+    /// Assignment numbers whose group children are currently collapsed in the tree
+    collapsed_assignments: HashSet<usize>,
+    /// Index into the current suggestion list highlighted for insertion
+    suggestion_selected: Option<usize>,
+    /// Whether the feedback editor is inserting text or interpreting motions
+    editor_mode: EditorMode,
+    /// Cursor position (byte index into `input`) inside the feedback editor
+    cursor_pos: usize,
+    /// Which pane of the active menu item currently receives input
+    focus: Focus,
+    /// The group list as of the last load or mutation, so rendering and
+    /// navigation don't re-query the store on every frame and every key press
+    groups: Vec<Group>,
 }
 
 impl Default for App {
@@ -71,7 +257,16 @@ impl Default for App {
         App {
             input: String::new(),
             input_mode: InputMode::Normal,
-            messages: Vec::new(),
+            editing_index: None,
+            editing_line: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            collapsed_assignments: HashSet::new(),
+            suggestion_selected: None,
+            editor_mode: EditorMode::Insert,
+            cursor_pos: 0,
+            focus: Focus::GroupList,
+            groups: Vec::new(),
         }
     }
 }
@@ -87,6 +282,18 @@ impl From<MenuItem> for usize {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let store: Box<dyn Store> = match SqliteStore::connect(SQLITE_URL) {
+        Ok(store) => Box::new(store),
+        Err(err) => {
+            eprintln!(
+                "could not open SQLite store at {}: {}; falling back to JSON store at {}",
+                SQLITE_URL, err, DB_PATH
+            );
+            Box::new(JsonStore::new(DB_PATH))
+        }
+    };
+    let store = store.as_ref();
+
     enable_raw_mode().expect("can run in raw mode");
 
     let (tx, rx) = mpsc::channel();
@@ -104,10 +311,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
 
-            if last_tick.elapsed() >= tick_rate {
-                if let Ok(_) = tx.send(Event::Tick) {
-                    last_tick = Instant::now();
-                }
+            if last_tick.elapsed() >= tick_rate && tx.send(Event::Tick).is_ok() {
+                last_tick = Instant::now();
             }
         }
     });
@@ -117,10 +322,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let menu_titles = vec!["Home", "Groups", "Add", "Delete", "Quit"];
+    let menu_titles = ["Home", "Groups", "Add", "Delete", "Quit"];
     let mut active_menu_item = MenuItem::Home;
     let mut group_list_state = ListState::default();
     group_list_state.select(Some(0));
+    let groups = store.load().unwrap_or_else(|err| {
+        eprintln!("could not load the group list: {}; starting with an empty list", err);
+        Vec::new()
+    });
+    let mut app = App {
+        groups,
+        ..App::default()
+    };
 
     loop {
         terminal.draw(|rect| {
@@ -182,53 +395,251 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             [Constraint::Percentage(20), Constraint::Percentage(80)].as_ref(),
                         )
                         .split(chunks[1]);
-                    let (left, right) = render_groups(&group_list_state);
+                    let (left, right) = render_groups(
+                        &group_list_state,
+                        &app.collapsed_assignments,
+                        &app.groups,
+                        app.focus,
+                    );
                     rect.render_stateful_widget(left, groups_chunks[0], &mut group_list_state);
                     rect.render_widget(right, groups_chunks[1]);
                 }
-                MenuItem::Editing => rect.render_widget(render_home(), chunks[1]),
+                MenuItem::Editing => {
+                    let editing_group = app
+                        .editing_index
+                        .and_then(|index| app.groups.get(index))
+                        .cloned();
+                    let suggestions = suggest::top_k(&app.input, &app.groups, 5);
+
+                    let editing_chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(
+                            [Constraint::Percentage(70), Constraint::Percentage(30)].as_ref(),
+                        )
+                        .split(chunks[1]);
+                    rect.render_widget(
+                        render_editor(&app, editing_group.as_ref()),
+                        editing_chunks[0],
+                    );
+                    rect.render_widget(
+                        render_suggestions(
+                            &suggestions,
+                            app.suggestion_selected,
+                            app.focus == Focus::Suggestions,
+                        ),
+                        editing_chunks[1],
+                    );
+                    if let InputMode::Editing = app.input_mode {
+                        // `render_editor` draws the input inline at `editing_line`'s row
+                        // when editing an existing line; otherwise every feedback line,
+                        // then a blank spacer, then the in-progress input as the last line.
+                        let cursor_x = editing_chunks[0].x + app.cursor_pos as u16 + 1;
+                        let input_row = match app.editing_line {
+                            Some(line_index) => line_index as u16,
+                            None => {
+                                let feedback_lines = editing_group
+                                    .as_ref()
+                                    .map_or(0, |group| group.feedback.len());
+                                feedback_lines as u16 + 1
+                            }
+                        };
+                        let cursor_y = editing_chunks[0].y + input_row + 1;
+                        rect.set_cursor(cursor_x, cursor_y);
+                    }
+                }
             }
             rect.render_widget(copyright, chunks[2]);
         })?;
 
         match rx.recv()? {
-            Event::Input(event) => match event.code {
-                KeyCode::Char('q') => {
-                    disable_raw_mode()?;
-                    terminal.show_cursor()?;
-                    terminal.clear()?;
-                    break;
-                }
-                KeyCode::Char('h') => active_menu_item = MenuItem::Home,
-                KeyCode::Char('g') => active_menu_item = MenuItem::Groups,
-                KeyCode::Char('a') => {
-                    add_random_group_to_db().expect("can add new random group");
-                }
-                KeyCode::Char('e') => active_menu_item = MenuItem::Editing,
-                KeyCode::Char('d') => {
-                    remove_group_at_index(&mut group_list_state).expect("can remove group");
-                }
-                KeyCode::Down => {
-                    if let Some(selected) = group_list_state.selected() {
-                        let amount_groups = read_db().expect("can fetch group list").len();
-                        if selected >= amount_groups - 1 {
-                            group_list_state.select(Some(0));
-                        } else {
-                            group_list_state.select(Some(selected + 1));
+            Event::Input(event) => match app.input_mode {
+                InputMode::Normal => match event.code {
+                    KeyCode::Char('q') => {
+                        disable_raw_mode()?;
+                        terminal.show_cursor()?;
+                        terminal.clear()?;
+                        break;
+                    }
+                    KeyCode::Char('h') => active_menu_item = MenuItem::Home,
+                    KeyCode::Char('g') => {
+                        active_menu_item = MenuItem::Groups;
+                        app.focus = Focus::GroupList;
+                    }
+                    KeyCode::Tab => {
+                        app.focus = app.focus.next_in_groups();
+                    }
+                    KeyCode::Char('a') => {
+                        push_undo_snapshot(&mut app);
+                        app.groups = add_random_group_to_db(store).expect("can add new random group");
+                    }
+                    KeyCode::Char('e') => {
+                        if let Some(group_index) = selected_group_index(
+                            &group_list_state,
+                            &app.collapsed_assignments,
+                            &app.groups,
+                        ) {
+                            app.editing_index = Some(group_index);
+                            app.editing_line = None;
+                            app.input.clear();
+                            app.cursor_pos = 0;
+                            app.editor_mode = EditorMode::Insert;
+                            app.focus = Focus::FeedbackEditor;
+                            app.input_mode = InputMode::Editing;
+                            active_menu_item = MenuItem::Editing;
                         }
                     }
-                }
-                KeyCode::Up => {
-                    if let Some(selected) = group_list_state.selected() {
-                        let amount_groups = read_db().expect("can fetch group list").len();
-                        if selected > 0 {
-                            group_list_state.select(Some(selected - 1));
-                        } else {
-                            group_list_state.select(Some(amount_groups - 1));
+                    KeyCode::Char('d') => {
+                        if let Some(group_index) = selected_group_index(
+                            &group_list_state,
+                            &app.collapsed_assignments,
+                            &app.groups,
+                        ) {
+                            push_undo_snapshot(&mut app);
+                            app.groups =
+                                store.remove_group(group_index).expect("can remove group");
+                            if let Some(selected) = group_list_state.selected() {
+                                if selected != 0 {
+                                    group_list_state.select(Some(selected - 1));
+                                }
+                            }
                         }
                     }
-                }
-                _ => {}
+                    KeyCode::Char('u') => {
+                        undo(&mut app, &mut group_list_state, store).expect("can undo last action");
+                    }
+                    KeyCode::Char('r') => {
+                        redo(&mut app, &mut group_list_state, store).expect("can redo last action");
+                    }
+                    KeyCode::Enter | KeyCode::Char(' ') if app.focus == Focus::GroupList => {
+                        toggle_selected_assignment(&mut app, &group_list_state);
+                    }
+                    KeyCode::Down if app.focus == Focus::GroupList => {
+                        if let Some(selected) = group_list_state.selected() {
+                            let amount_visible =
+                                visible_tree_len(&app.collapsed_assignments, &app.groups);
+                            if amount_visible > 0 {
+                                if selected >= amount_visible - 1 {
+                                    group_list_state.select(Some(0));
+                                } else {
+                                    group_list_state.select(Some(selected + 1));
+                                }
+                            }
+                        }
+                    }
+                    KeyCode::Up if app.focus == Focus::GroupList => {
+                        if let Some(selected) = group_list_state.selected() {
+                            let amount_visible =
+                                visible_tree_len(&app.collapsed_assignments, &app.groups);
+                            if amount_visible > 0 {
+                                if selected > 0 {
+                                    group_list_state.select(Some(selected - 1));
+                                } else {
+                                    group_list_state.select(Some(amount_visible - 1));
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                InputMode::Editing => match app.editor_mode {
+                    EditorMode::Insert => match event.code {
+                        KeyCode::Tab => {
+                            cycle_suggestion(&mut app, true);
+                        }
+                        KeyCode::Up if app.focus == Focus::Suggestions => {
+                            cycle_suggestion(&mut app, false);
+                        }
+                        KeyCode::Down if app.focus == Focus::Suggestions => {
+                            cycle_suggestion(&mut app, true);
+                        }
+                        KeyCode::Enter => {
+                            if let Some(selected) = app.suggestion_selected.take() {
+                                let suggestions = suggest::top_k(&app.input, &app.groups, 5);
+                                if let Some(suggestion) = suggestions.get(selected) {
+                                    app.input = suggestion.text.clone();
+                                    app.cursor_pos = app.input.len();
+                                }
+                            } else if let Some(index) = app.editing_index {
+                                if !app.input.trim().is_empty() {
+                                    push_undo_snapshot(&mut app);
+                                    app.groups = match app.editing_line {
+                                        Some(line_index) => store
+                                            .set_feedback_line(index, line_index, app.input.clone())
+                                            .expect("can update feedback line"),
+                                        None => store
+                                            .add_feedback_line(index, app.input.clone())
+                                            .expect("can append feedback line"),
+                                    };
+                                }
+                                app.input.clear();
+                                app.editing_line = None;
+                                app.cursor_pos = 0;
+                            }
+                        }
+                        KeyCode::Char(c) => {
+                            let pos = app.cursor_pos.min(app.input.len());
+                            app.input.insert(pos, c);
+                            app.cursor_pos = pos + c.len_utf8();
+                            app.suggestion_selected = None;
+                        }
+                        KeyCode::Backspace => {
+                            let previous = app.input[..app.cursor_pos].chars().next_back();
+                            if let Some(c) = previous {
+                                app.cursor_pos -= c.len_utf8();
+                                app.input.remove(app.cursor_pos);
+                            }
+                            app.suggestion_selected = None;
+                        }
+                        KeyCode::Esc => {
+                            app.editor_mode = EditorMode::Command;
+                        }
+                        _ => {}
+                    },
+                    EditorMode::Command => match event.code {
+                        KeyCode::Tab => {
+                            app.focus = app.focus.next_in_editing();
+                        }
+                        KeyCode::Up if app.focus == Focus::Suggestions => {
+                            cycle_suggestion(&mut app, false);
+                        }
+                        KeyCode::Down if app.focus == Focus::Suggestions => {
+                            cycle_suggestion(&mut app, true);
+                        }
+                        KeyCode::Up if app.focus == Focus::FeedbackEditor => {
+                            select_editing_line(&mut app, false);
+                        }
+                        KeyCode::Down if app.focus == Focus::FeedbackEditor => {
+                            select_editing_line(&mut app, true);
+                        }
+                        KeyCode::Char('i') => app.editor_mode = EditorMode::Insert,
+                        KeyCode::Char('0') => app.cursor_pos = 0,
+                        KeyCode::Char('$') => app.cursor_pos = app.input.len(),
+                        KeyCode::Char('^') => {
+                            app.cursor_pos = app
+                                .input
+                                .find(|c: char| !c.is_whitespace())
+                                .unwrap_or(0);
+                        }
+                        KeyCode::Char('w') => {
+                            app.cursor_pos = next_word_boundary(&app.input, app.cursor_pos);
+                        }
+                        KeyCode::Char('b') => {
+                            app.cursor_pos = prev_word_boundary(&app.input, app.cursor_pos);
+                        }
+                        KeyCode::Esc => {
+                            app.input.clear();
+                            app.editing_index = None;
+                            app.editing_line = None;
+                            app.suggestion_selected = None;
+                            app.cursor_pos = 0;
+                            app.editor_mode = EditorMode::Insert;
+                            app.focus = Focus::GroupList;
+                            app.input_mode = InputMode::Normal;
+                            active_menu_item = MenuItem::Groups;
+                        }
+                        _ => {}
+                    },
+                },
             },
             Event::Tick => {}
         }
@@ -269,45 +680,66 @@ fn render_home<'a>() -> Paragraph<'a> {
     home
 }
 
-fn render_groups<'a>(group_list_state: &ListState) -> (List<'a>, Table<'a>) {
+fn render_groups<'a>(
+    group_list_state: &ListState,
+    collapsed: &HashSet<usize>,
+    group_list: &[Group],
+    focus: Focus,
+) -> (List<'a>, Table<'a>) {
+    let (list_style, list_border) = focus_chrome(focus == Focus::GroupList);
+    let (detail_style, detail_border) = focus_chrome(focus == Focus::Detail);
+
     let groups = Block::default()
         .borders(Borders::ALL)
-        .style(Style::default().fg(Color::White))
+        .style(list_style)
         .title("Groups")
-        .border_type(BorderType::Plain);
+        .border_type(list_border);
 
-    let group_list = read_db().expect("can fetch group list");
-    let items: Vec<_> = group_list
+    let tree = build_group_tree(group_list, collapsed);
+    let visible: Vec<&TreeItem> = tree.iter().filter(|item| item.is_visible()).collect();
+
+    let items: Vec<_> = visible
         .iter()
-        .map(|group| {
-            ListItem::new(Spans::from(vec![Span::styled(
-                group.name.clone(),
-                Style::default(),
-            )]))
+        .map(|item| match item {
+            TreeItem::Assignment {
+                assignment,
+                group_count,
+                info,
+            } => {
+                let arrow = if info.collapsed { "▶" } else { "▼" };
+                ListItem::new(Spans::from(vec![Span::styled(
+                    format!("{} Assignment {} ({})", arrow, assignment, group_count),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )]))
+            }
+            TreeItem::Group { group_index, info } => ListItem::new(Spans::from(vec![Span::raw(
+                format!("{}{}", "  ".repeat(info.indent), group_list[*group_index].name),
+            )])),
         })
         .collect();
 
-    let selected_group = group_list
-        .get(
-            group_list_state
-                .selected()
-                .expect("there is always a selected group"),
-        )
-        .expect("exists")
-        .clone();
-
-    let list = List::new(items).block(groups).highlight_style(
-        Style::default()
-            .bg(Color::Yellow)
-            .fg(Color::Black)
-            .add_modifier(Modifier::BOLD),
-    );
+    let selected_item = group_list_state.selected().and_then(|i| visible.get(i));
 
-    let group_detail = Table::new(vec![Row::new(vec![
-        Cell::from(Span::raw(selected_group.name.to_string())),
-        Cell::from(Span::raw(selected_group.assignment.to_string())),
-        Cell::from(Span::raw(selected_group.feedback.concat())),
-    ])])
+    let group_detail = match selected_item {
+        Some(TreeItem::Group { group_index, .. }) => {
+            let group = &group_list[*group_index];
+            Table::new(vec![Row::new(vec![
+                Cell::from(Span::raw(group.name.to_string())),
+                Cell::from(Span::raw(group.assignment.to_string())),
+                Cell::from(Span::raw(group.feedback.concat())),
+            ])])
+        }
+        Some(TreeItem::Assignment {
+            assignment,
+            group_count,
+            ..
+        }) => Table::new(vec![Row::new(vec![
+            Cell::from(Span::raw(format!("Assignment {}", assignment))),
+            Cell::from(Span::raw(assignment.to_string())),
+            Cell::from(Span::raw(format!("{} group(s)", group_count))),
+        ])]),
+        None => Table::new(vec![]),
+    }
     .header(Row::new(vec![
         Cell::from(Span::styled(
             "Type",
@@ -325,9 +757,9 @@ fn render_groups<'a>(group_list_state: &ListState) -> (List<'a>, Table<'a>) {
     .block(
         Block::default()
             .borders(Borders::ALL)
-            .style(Style::default().fg(Color::White))
+            .style(detail_style)
             .title("Detail")
-            .border_type(BorderType::Plain),
+            .border_type(detail_border),
     )
     .widths(&[
         Constraint::Percentage(10),
@@ -335,46 +767,241 @@ fn render_groups<'a>(group_list_state: &ListState) -> (List<'a>, Table<'a>) {
         Constraint::Percentage(100),
     ]);
 
+    let list = List::new(items).block(groups).highlight_style(
+        Style::default()
+            .bg(Color::Yellow)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD),
+    );
+
     (list, group_detail)
 }
 
-fn read_db() -> Result<Vec<Group>, Error> {
-    let db_content = fs::read_to_string(DB_PATH)?;
-    let parsed: Vec<Group> = serde_json::from_str(&db_content)?;
-    Ok(parsed)
+fn visible_tree_len(collapsed: &HashSet<usize>, group_list: &[Group]) -> usize {
+    build_group_tree(group_list, collapsed)
+        .iter()
+        .filter(|item| item.is_visible())
+        .count()
+}
+
+/// Maps the currently selected row in the visible tree back to an index into the
+/// flat group list, or `None` if an assignment header is selected.
+fn selected_group_index(
+    group_list_state: &ListState,
+    collapsed: &HashSet<usize>,
+    group_list: &[Group],
+) -> Option<usize> {
+    let tree = build_group_tree(group_list, collapsed);
+    let visible: Vec<&TreeItem> = tree.iter().filter(|item| item.is_visible()).collect();
+    match visible.get(group_list_state.selected()?)? {
+        TreeItem::Group { group_index, .. } => Some(*group_index),
+        TreeItem::Assignment { .. } => None,
+    }
+}
+
+fn toggle_selected_assignment(app: &mut App, group_list_state: &ListState) {
+    let tree = build_group_tree(&app.groups, &app.collapsed_assignments);
+    let visible: Vec<&TreeItem> = tree.iter().filter(|item| item.is_visible()).collect();
+    if let Some(TreeItem::Assignment { assignment, .. }) =
+        group_list_state.selected().and_then(|i| visible.get(i))
+    {
+        if !app.collapsed_assignments.insert(*assignment) {
+            app.collapsed_assignments.remove(assignment);
+        }
+    }
+}
+
+fn render_editor<'a>(app: &App, group: Option<&Group>) -> Paragraph<'a> {
+    let title = match (group, app.editing_line) {
+        (Some(group), Some(line_index)) => {
+            format!("Editing feedback for {} (line {})", group.name, line_index + 1)
+        }
+        (Some(group), None) => format!("Editing feedback for {}", group.name),
+        (None, _) => "Editing feedback".to_string(),
+    };
+
+    let input_line = Spans::from(vec![Span::styled(
+        app.input.clone(),
+        Style::default().fg(Color::Yellow),
+    )]);
+
+    let mut lines: Vec<Spans> = group
+        .map(|group| {
+            group
+                .feedback
+                .iter()
+                .enumerate()
+                .map(|(index, line)| {
+                    if Some(index) == app.editing_line {
+                        input_line.clone()
+                    } else {
+                        Spans::from(vec![Span::raw(line.clone())])
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if app.editing_line.is_none() {
+        lines.push(Spans::from(vec![Span::raw("")]));
+        lines.push(input_line);
+    }
+
+    let (style, border) = focus_chrome(app.focus == Focus::FeedbackEditor);
+
+    Paragraph::new(lines).alignment(Alignment::Left).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(style)
+            .title(title)
+            .border_type(border),
+    )
+}
+
+fn render_suggestions<'a>(
+    suggestions: &[suggest::Suggestion],
+    selected: Option<usize>,
+    focused: bool,
+) -> List<'a> {
+    let (style, border) = focus_chrome(focused);
+    let items: Vec<_> = suggestions
+        .iter()
+        .enumerate()
+        .map(|(index, suggestion)| {
+            let style = if Some(index) == selected {
+                Style::default()
+                    .bg(Color::Yellow)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Spans::from(vec![Span::styled(
+                suggestion.text.clone(),
+                style,
+            )]))
+        })
+        .collect();
+
+    List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(style)
+            .title("Suggestions (Tab to cycle, Enter to insert)")
+            .border_type(border),
+    )
+}
+
+/// Advances `app.suggestion_selected` through the current suggestion list
+fn cycle_suggestion(app: &mut App, forward: bool) {
+    let suggestions = suggest::top_k(&app.input, &app.groups, 5);
+    if suggestions.is_empty() {
+        return;
+    }
+    app.suggestion_selected = Some(match app.suggestion_selected {
+        Some(selected) if forward => (selected + 1) % suggestions.len(),
+        Some(selected) => (selected + suggestions.len() - 1) % suggestions.len(),
+        None => 0,
+    });
+}
+
+/// Advances `app.editing_line` through the edited group's committed feedback
+/// lines, wrapping through `None` (a new, not yet committed line) at both
+/// ends, and loads the newly selected line's text into `input` for editing.
+fn select_editing_line(app: &mut App, forward: bool) {
+    let feedback_len = app
+        .editing_index
+        .and_then(|index| app.groups.get(index))
+        .map_or(0, |group| group.feedback.len());
+    if feedback_len == 0 {
+        return;
+    }
+
+    app.editing_line = match app.editing_line {
+        Some(selected) if forward => {
+            if selected + 1 < feedback_len {
+                Some(selected + 1)
+            } else {
+                None
+            }
+        }
+        Some(selected) => {
+            if selected > 0 {
+                Some(selected - 1)
+            } else {
+                None
+            }
+        }
+        None if forward => Some(0),
+        None => Some(feedback_len - 1),
+    };
+
+    app.input = match app.editing_line {
+        Some(line_index) => app
+            .editing_index
+            .and_then(|index| app.groups.get(index))
+            .and_then(|group| group.feedback.get(line_index))
+            .cloned()
+            .unwrap_or_default(),
+        None => String::new(),
+    };
+    app.cursor_pos = app.input.len();
+}
+
+fn push_undo_snapshot(app: &mut App) {
+    app.undo_stack.push(app.groups.clone());
+    app.redo_stack.clear();
 }
 
-fn add_random_group_to_db() -> Result<Vec<Group>, Error> {
+fn sync_selection(group_list_state: &mut ListState, amount_groups: usize) {
+    if amount_groups == 0 {
+        group_list_state.select(None);
+    } else {
+        let selected = group_list_state.selected().unwrap_or(0);
+        group_list_state.select(Some(selected.min(amount_groups - 1)));
+    }
+}
+
+fn undo(app: &mut App, group_list_state: &mut ListState, store: &dyn Store) -> Result<(), Error> {
+    if let Some(previous) = app.undo_stack.pop() {
+        let current = app.groups.clone();
+        let visible_count = build_group_tree(&previous, &app.collapsed_assignments)
+            .iter()
+            .filter(|item| item.is_visible())
+            .count();
+        app.groups = store.replace_all(previous)?;
+        app.redo_stack.push(current);
+        sync_selection(group_list_state, visible_count);
+    }
+    Ok(())
+}
+
+fn redo(app: &mut App, group_list_state: &mut ListState, store: &dyn Store) -> Result<(), Error> {
+    if let Some(next) = app.redo_stack.pop() {
+        let current = app.groups.clone();
+        let visible_count = build_group_tree(&next, &app.collapsed_assignments)
+            .iter()
+            .filter(|item| item.is_visible())
+            .count();
+        app.groups = store.replace_all(next)?;
+        app.undo_stack.push(current);
+        sync_selection(group_list_state, visible_count);
+    }
+    Ok(())
+}
+
+fn add_random_group_to_db(store: &dyn Store) -> Result<Vec<Group>, Error> {
     let mut rng = rand::thread_rng();
-    let db_content = fs::read_to_string(DB_PATH)?;
-    let mut parsed: Vec<Group> = serde_json::from_str(&db_content)?;
     let mut textvector = Vec::new();
     let bottomtext = "This assignment was graded by: Tom Koning. E-mail: tom.koning@ru.nl.";
     textvector.push("feedback".to_string());
 
     let random_group = Group {
-        name: format!("Group {}", rng.gen_range(0, 10).to_string()),
+        name: format!("Group {}", rng.gen_range(0, 10)),
         assignment: rng.gen_range(0, 10),
         feedback: textvector,
         footnote: bottomtext.to_string(),
     };
 
-    parsed.push(random_group);
-    fs::write(DB_PATH, &serde_json::to_vec(&parsed)?)?;
-    Ok(parsed)
-}
-
-fn remove_group_at_index(group_list_state: &mut ListState) -> Result<(), Error> {
-    if let Some(selected) = group_list_state.selected() {
-        let db_content = fs::read_to_string(DB_PATH)?;
-        let mut parsed: Vec<Group> = serde_json::from_str(&db_content)?;
-        if parsed.len() != 1 {
-            parsed.remove(selected);
-            fs::write(DB_PATH, &serde_json::to_vec(&parsed)?)?;
-            if selected != 0 {
-                group_list_state.select(Some(selected - 1));
-            }
-        }
-    }
-    Ok(())
+    store.add_group(random_group)
 }
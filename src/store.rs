@@ -0,0 +1,288 @@
+use crate::{Error, Group};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Row, SqlitePool};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use tokio::runtime::Runtime;
+
+/// Creates the parent directory of `path`, if any, so a file can be written
+/// there on a clean checkout where `./data/` doesn't exist yet.
+fn create_parent_dir(path: &str) -> Result<(), Error> {
+    if let Some(parent) = Path::new(path).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    Ok(())
+}
+
+/// Abstracts over how the group list is persisted, so the original flat-file
+/// format stays available while SQLite is the default, scalable backend.
+pub trait Store {
+    fn load(&self) -> Result<Vec<Group>, Error>;
+    fn add_group(&self, group: Group) -> Result<Vec<Group>, Error>;
+    fn remove_group(&self, index: usize) -> Result<Vec<Group>, Error>;
+    fn add_feedback_line(&self, index: usize, line: String) -> Result<Vec<Group>, Error>;
+    fn set_feedback_line(
+        &self,
+        index: usize,
+        line_index: usize,
+        line: String,
+    ) -> Result<Vec<Group>, Error>;
+    fn replace_all(&self, groups: Vec<Group>) -> Result<Vec<Group>, Error>;
+}
+
+/// The original backend: the whole list is re-parsed and rewritten on every
+/// mutation. Kept around as the fallback used when the SQLite store cannot be opened.
+pub struct JsonStore {
+    path: &'static str,
+}
+
+impl JsonStore {
+    pub fn new(path: &'static str) -> Self {
+        JsonStore { path }
+    }
+
+    fn write(&self, groups: &[Group]) -> Result<(), Error> {
+        create_parent_dir(self.path)?;
+        fs::write(self.path, &serde_json::to_vec(groups)?)?;
+        Ok(())
+    }
+}
+
+impl Store for JsonStore {
+    fn load(&self) -> Result<Vec<Group>, Error> {
+        let content = fs::read_to_string(self.path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn add_group(&self, group: Group) -> Result<Vec<Group>, Error> {
+        let mut groups = self.load()?;
+        groups.push(group);
+        self.write(&groups)?;
+        Ok(groups)
+    }
+
+    fn remove_group(&self, index: usize) -> Result<Vec<Group>, Error> {
+        let mut groups = self.load()?;
+        if groups.len() != 1 {
+            groups.remove(index);
+            self.write(&groups)?;
+        }
+        Ok(groups)
+    }
+
+    fn add_feedback_line(&self, index: usize, line: String) -> Result<Vec<Group>, Error> {
+        let mut groups = self.load()?;
+        if let Some(group) = groups.get_mut(index) {
+            group.feedback.push(line);
+        }
+        self.write(&groups)?;
+        Ok(groups)
+    }
+
+    fn set_feedback_line(
+        &self,
+        index: usize,
+        line_index: usize,
+        line: String,
+    ) -> Result<Vec<Group>, Error> {
+        let mut groups = self.load()?;
+        if let Some(existing) = groups
+            .get_mut(index)
+            .and_then(|group| group.feedback.get_mut(line_index))
+        {
+            *existing = line;
+        }
+        self.write(&groups)?;
+        Ok(groups)
+    }
+
+    fn replace_all(&self, groups: Vec<Group>) -> Result<Vec<Group>, Error> {
+        self.write(&groups)?;
+        Ok(groups)
+    }
+}
+
+/// The scalable default backend: a `groups` table joined with a `feedback`
+/// table, queried directly instead of deserializing the whole database on
+/// every frame. The pool is async, but the rest of the app's event loop is
+/// synchronous, so every call blocks on a runtime owned by the store.
+pub struct SqliteStore {
+    pool: SqlitePool,
+    rt: Runtime,
+}
+
+impl SqliteStore {
+    pub fn connect(database_url: &str) -> Result<Self, Error> {
+        // `create_if_missing(true)` only covers the DB file itself: it won't
+        // create a missing `./data/` directory, so do that ourselves first.
+        create_parent_dir(database_url.trim_start_matches("sqlite://"))?;
+
+        let rt = Runtime::new().expect("can start the sqlite runtime");
+        let pool = rt.block_on(async {
+            let options = SqliteConnectOptions::from_str(database_url)?
+                .create_if_missing(true)
+                // `ON DELETE CASCADE` on the `feedback` table is only enforced
+                // while this pragma is set, and it's per-connection, so set it
+                // on every connection the pool opens rather than once.
+                .foreign_keys(true);
+            let pool = SqlitePoolOptions::new()
+                .max_connections(5)
+                .connect_with(options)
+                .await?;
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS groups (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    assignment INTEGER NOT NULL,
+                    footnote TEXT NOT NULL
+                )",
+            )
+            .execute(&pool)
+            .await?;
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS feedback (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    group_id INTEGER NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+                    position INTEGER NOT NULL,
+                    line TEXT NOT NULL
+                )",
+            )
+            .execute(&pool)
+            .await?;
+            Ok::<_, sqlx::Error>(pool)
+        })?;
+        Ok(SqliteStore { pool, rt })
+    }
+
+    fn group_ids(&self) -> Result<Vec<i64>, Error> {
+        self.rt.block_on(async {
+            let rows = sqlx::query("SELECT id FROM groups ORDER BY id")
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.iter().map(|row| row.get::<i64, _>("id")).collect())
+        })
+    }
+}
+
+impl Store for SqliteStore {
+    fn load(&self) -> Result<Vec<Group>, Error> {
+        self.rt.block_on(async {
+            let group_rows =
+                sqlx::query("SELECT id, name, assignment, footnote FROM groups ORDER BY id")
+                    .fetch_all(&self.pool)
+                    .await?;
+
+            let mut groups = Vec::with_capacity(group_rows.len());
+            for row in group_rows {
+                let id: i64 = row.get("id");
+                let feedback_rows =
+                    sqlx::query("SELECT line FROM feedback WHERE group_id = ? ORDER BY position")
+                        .bind(id)
+                        .fetch_all(&self.pool)
+                        .await?;
+
+                groups.push(Group {
+                    name: row.get("name"),
+                    assignment: row.get::<i64, _>("assignment") as usize,
+                    feedback: feedback_rows.iter().map(|line| line.get("line")).collect(),
+                    footnote: row.get("footnote"),
+                });
+            }
+            Ok(groups)
+        })
+    }
+
+    fn add_group(&self, group: Group) -> Result<Vec<Group>, Error> {
+        self.rt.block_on(async {
+            let inserted =
+                sqlx::query("INSERT INTO groups (name, assignment, footnote) VALUES (?, ?, ?)")
+                    .bind(&group.name)
+                    .bind(group.assignment as i64)
+                    .bind(&group.footnote)
+                    .execute(&self.pool)
+                    .await?;
+            let group_id = inserted.last_insert_rowid();
+            for (position, line) in group.feedback.iter().enumerate() {
+                sqlx::query("INSERT INTO feedback (group_id, position, line) VALUES (?, ?, ?)")
+                    .bind(group_id)
+                    .bind(position as i64)
+                    .bind(line)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            Ok::<_, sqlx::Error>(())
+        })?;
+        self.load()
+    }
+
+    fn remove_group(&self, index: usize) -> Result<Vec<Group>, Error> {
+        let ids = self.group_ids()?;
+        if ids.len() != 1 {
+            if let Some(&group_id) = ids.get(index) {
+                self.rt.block_on(async {
+                    sqlx::query("DELETE FROM groups WHERE id = ?")
+                        .bind(group_id)
+                        .execute(&self.pool)
+                        .await
+                })?;
+            }
+        }
+        self.load()
+    }
+
+    fn add_feedback_line(&self, index: usize, line: String) -> Result<Vec<Group>, Error> {
+        let ids = self.group_ids()?;
+        if let Some(&group_id) = ids.get(index) {
+            self.rt.block_on(async {
+                let position: i64 =
+                    sqlx::query("SELECT COUNT(*) AS count FROM feedback WHERE group_id = ?")
+                        .bind(group_id)
+                        .fetch_one(&self.pool)
+                        .await?
+                        .get("count");
+                sqlx::query("INSERT INTO feedback (group_id, position, line) VALUES (?, ?, ?)")
+                    .bind(group_id)
+                    .bind(position)
+                    .bind(&line)
+                    .execute(&self.pool)
+                    .await
+            })?;
+        }
+        self.load()
+    }
+
+    fn set_feedback_line(
+        &self,
+        index: usize,
+        line_index: usize,
+        line: String,
+    ) -> Result<Vec<Group>, Error> {
+        let ids = self.group_ids()?;
+        if let Some(&group_id) = ids.get(index) {
+            self.rt.block_on(async {
+                sqlx::query("UPDATE feedback SET line = ? WHERE group_id = ? AND position = ?")
+                    .bind(&line)
+                    .bind(group_id)
+                    .bind(line_index as i64)
+                    .execute(&self.pool)
+                    .await
+            })?;
+        }
+        self.load()
+    }
+
+    fn replace_all(&self, groups: Vec<Group>) -> Result<Vec<Group>, Error> {
+        self.rt.block_on(async {
+            sqlx::query("DELETE FROM feedback").execute(&self.pool).await?;
+            sqlx::query("DELETE FROM groups").execute(&self.pool).await?;
+            Ok::<_, sqlx::Error>(())
+        })?;
+        for group in groups {
+            self.add_group(group)?;
+        }
+        self.load()
+    }
+}
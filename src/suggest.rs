@@ -0,0 +1,115 @@
+use crate::Group;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A previously written feedback line ranked by similarity to the in-progress input
+pub struct Suggestion {
+    pub text: String,
+    pub score: f64,
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn term_counts(tokens: &[String]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for token in tokens {
+        *counts.entry(token.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Every non-empty feedback line across all groups, paired with its term counts
+fn corpus_term_counts(groups: &[Group]) -> Vec<(String, HashMap<String, usize>)> {
+    groups
+        .iter()
+        .flat_map(|group| group.feedback.iter())
+        .map(|line| line.as_str())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| (line.to_string(), term_counts(&tokenize(line))))
+        .collect()
+}
+
+fn document_frequency<'a>(
+    documents: impl Iterator<Item = &'a HashMap<String, usize>>,
+) -> HashMap<String, usize> {
+    let mut frequency = HashMap::new();
+    for terms in documents {
+        for term in terms.keys() {
+            *frequency.entry(term.clone()).or_insert(0) += 1;
+        }
+    }
+    frequency
+}
+
+fn tfidf_vector(
+    terms: &HashMap<String, usize>,
+    document_frequency: &HashMap<String, usize>,
+    total_documents: f64,
+) -> HashMap<String, f64> {
+    terms
+        .iter()
+        .map(|(term, count)| {
+            let df = document_frequency.get(term).copied().unwrap_or(1) as f64;
+            let weight = *count as f64 * (total_documents / df).ln();
+            (term.clone(), weight)
+        })
+        .collect()
+}
+
+fn cosine_similarity(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a
+        .iter()
+        .map(|(term, weight)| weight * b.get(term).copied().unwrap_or(0.0))
+        .sum();
+    let norm_a = a.values().map(|weight| weight * weight).sum::<f64>().sqrt();
+    let norm_b = b.values().map(|weight| weight * weight).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Ranks every feedback line ever written across `groups` by cosine similarity
+/// to `input`'s TF-IDF vector, returning the top `k`. The index is rebuilt from
+/// scratch on each call instead of being maintained incrementally, so it is
+/// always in sync with the latest edits.
+pub fn top_k(input: &str, groups: &[Group], k: usize) -> Vec<Suggestion> {
+    if input.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let corpus = corpus_term_counts(groups);
+    if corpus.is_empty() {
+        return Vec::new();
+    }
+
+    let document_frequency = document_frequency(corpus.iter().map(|(_, terms)| terms));
+    let total_documents = corpus.len() as f64;
+    let query_vector = tfidf_vector(
+        &term_counts(&tokenize(input)),
+        &document_frequency,
+        total_documents,
+    );
+
+    let mut suggestions: Vec<Suggestion> = corpus
+        .into_iter()
+        .map(|(text, terms)| Suggestion {
+            score: cosine_similarity(
+                &query_vector,
+                &tfidf_vector(&terms, &document_frequency, total_documents),
+            ),
+            text,
+        })
+        .filter(|suggestion| suggestion.score > 0.0)
+        .collect();
+
+    suggestions.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    suggestions.truncate(k);
+    suggestions
+}